@@ -0,0 +1,24 @@
+use std::{fmt, path::PathBuf};
+
+/// Errors produced by this crate's safe wrappers around the zsh module API.
+#[derive(Debug)]
+pub enum Zerror {
+    /// A path passed to a [`crate::types::FilePath`] constructor does not exist on disk.
+    ///
+    /// Also returned by `FilePath::canonicalize` and `FilePath::open` for any I/O failure,
+    /// not only a missing path.
+    FileNotFound(PathBuf),
+    /// A dependency named via [`crate::Module::require`] could not be loaded by zsh.
+    ModuleLoadFailed(String),
+}
+
+impl fmt::Display for Zerror {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileNotFound(path) => write!(f, "file not found: {}", path.display()),
+            Self::ModuleLoadFailed(name) => write!(f, "failed to load dependency module: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for Zerror {}
@@ -1,23 +1,44 @@
-use std::{
-    ffi::{c_char, c_int, CStr},
-    sync::atomic::AtomicBool,
-};
+use std::ffi::{c_char, c_int, CStr};
 
-use crate::{log, options::Opts, to_cstr, AnyError, Module};
+use crate::{log, module::WrapperAction, options::Opts, to_cstr, AnyError, Module};
 
 use parking_lot::Mutex;
 use zsh_sys as zsys;
 
+/// A snapshot of the last panic caught while running module code.
+///
+/// Captured by the hook installed in `setup_`, independently of which trampoline was
+/// unwinding at the time, so it survives past the `catch_unwind` that stopped the unwind.
+#[derive(Debug, Clone)]
+pub struct PanicRecord {
+    /// The panic payload, downcast to a string where possible.
+    pub message: String,
+    /// Where the panic occurred, as rendered by [`std::panic::Location`].
+    pub location: String,
+    /// A captured backtrace, when `cfg(debug_assertions)` makes the allocation worth it.
+    pub backtrace: Option<String>,
+}
+
+impl std::fmt::Display for PanicRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "panicked at {}: {}", self.location, self.message)?;
+        if let Some(backtrace) = &self.backtrace {
+            write!(f, "\n{backtrace}")?;
+        }
+        Ok(())
+    }
+}
+
 struct ModuleHolder {
     module: Mutex<Option<Module>>,
-    panicked: AtomicBool,
+    last_panic: Mutex<Option<PanicRecord>>,
 }
 
 impl ModuleHolder {
     const fn empty() -> Self {
         Self {
             module: parking_lot::const_mutex(None),
-            panicked: AtomicBool::new(false),
+            last_panic: parking_lot::const_mutex(None),
         }
     }
 }
@@ -46,7 +67,7 @@ extern "C" fn builtin_callback(
     opts: *mut zsys::options,
     _: i32,
 ) -> i32 {
-    handle_panic(name, || {
+    handle_panic(|| {
         let args = unsafe { strings_from_ptr(std::mem::transmute(args)) };
         let name = unsafe { CStr::from_ptr(name) };
         let opts = unsafe { Opts::from_raw(opts) };
@@ -75,13 +96,96 @@ extern "C" fn builtin_callback(
     .unwrap_or(65)
 }
 
+extern "C" fn param_get(pm: *mut zsys::Param) -> *mut c_char {
+    handle_panic(|| {
+        let name = unsafe { CStr::from_ptr((*pm).node.nam) };
+        let mut module = get_mod();
+        let crate::Module {
+            paramtable,
+            user_data,
+            ..
+        } = &mut *module;
+        let (get, _) = paramtable
+            .get_mut(name)
+            .expect("Failed to find parameter name");
+        match get(&mut **user_data) {
+            Ok(value) => unsafe { zsys::ztrdup(to_cstr(value).as_ptr()) },
+            Err(e) => {
+                log::error_named(name, to_cstr(e.to_string()));
+                std::ptr::null_mut()
+            }
+        }
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+extern "C" fn param_set(pm: *mut zsys::Param, value: *mut c_char) {
+    handle_panic(|| {
+        let name = unsafe { CStr::from_ptr((*pm).node.nam) };
+        // zsh passes a NULL value to signal that the parameter is being unset.
+        let value = if value.is_null() {
+            None
+        } else {
+            Some(
+                unsafe { CStr::from_ptr(value) }
+                    .to_str()
+                    .expect("Failed to parse parameter value"),
+            )
+        };
+        let mut module = get_mod();
+        let crate::Module {
+            paramtable,
+            user_data,
+            ..
+        } = &mut *module;
+        let (_, set) = paramtable
+            .get_mut(name)
+            .expect("Failed to find parameter name");
+        if let Err(e) = set(&mut **user_data, value) {
+            log::error_named(name, to_cstr(e.to_string()));
+        }
+    });
+}
+
+extern "C" fn mathfn_callback(
+    name: *mut c_char,
+    _argc: c_int,
+    argv: *mut *mut c_char,
+    _id: c_int,
+) -> f64 {
+    handle_panic(|| {
+        let args = unsafe { strings_from_ptr(std::mem::transmute(argv)) };
+        let name = unsafe { CStr::from_ptr(name) };
+
+        let mut module = get_mod();
+        let crate::Module {
+            mathfntable,
+            user_data,
+            ..
+        } = &mut *module;
+        let f = mathfntable
+            .get_mut(name)
+            .expect("Failed to find math function name");
+        match f(&mut **user_data, &args) {
+            Ok(value) => value,
+            Err(e) => {
+                log::error_named(name, to_cstr(e.to_string()));
+                0.0
+            }
+        }
+    })
+    .unwrap_or(0.0)
+}
+
 fn set_mod(module: Module) {
     *MODULE.module.lock() = Some(module);
 }
 
 fn drop_mod() {
-    if !panicked() {
-        MODULE.module.lock().take();
+    let taken = MODULE.module.lock().take();
+    if let Err(err) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(taken))) {
+        drop(err);
+        crate::error!("Panicked again while tearing down a panicked module");
     }
 }
 
@@ -95,32 +199,89 @@ unsafe fn mod_get_name<'a>(module: zsys::Module) -> &'a CStr {
     CStr::from_ptr((*module).node.nam)
 }
 
-fn panicked() -> bool {
-    MODULE.panicked.load(std::sync::atomic::Ordering::Acquire)
+/// Installs the panic hook that records [`PanicRecord`]s, called once from `setup_`.
+///
+/// The hook runs before unwinding starts, so it captures accurate message/location/backtrace
+/// information regardless of which trampoline's `catch_unwind` ultimately stops the unwind.
+fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "no additional information".to_string());
+        let location = info
+            .location()
+            .map(|l| l.to_string())
+            .unwrap_or_else(|| "unknown location".to_string());
+        // Capturing a backtrace allocates; skip it in release builds.
+        let backtrace = if cfg!(debug_assertions) {
+            Some(std::backtrace::Backtrace::force_capture().to_string())
+        } else {
+            None
+        };
+        let record = PanicRecord {
+            message,
+            location,
+            backtrace,
+        };
+        log::error(record.to_string());
+        *MODULE.last_panic.lock() = Some(record);
+    }));
 }
 
-fn handle_panic<F, N, R>(name: N, cb: F) -> Option<R>
+/// Returns a copy of the last recorded panic, if any module code has panicked.
+pub fn last_panic() -> Option<PanicRecord> {
+    MODULE.last_panic.lock().clone()
+}
+
+/// Clears the last recorded panic, e.g. once the user has handled it.
+pub fn clear_last_panic() {
+    MODULE.last_panic.lock().take();
+}
+
+fn handle_panic<F, R>(cb: F) -> Option<R>
 where
     F: FnOnce() -> R + std::panic::UnwindSafe,
-    N: std::fmt::Debug,
 {
-    let res = std::panic::catch_unwind(|| cb());
-    match res {
-        Ok(ret) => Some(ret),
-        Err(err) => {
-            MODULE
-                .panicked
-                .store(true, std::sync::atomic::Ordering::Release);
-            if let Some(msg) = err.downcast_ref::<&str>() {
-                crate::error!("{:?} Panic: {}", name, msg);
-            } else if let Some(msg) = err.downcast_ref::<String>() {
-                crate::error!("{:?} Panic: {}", name, msg);
-            } else {
-                crate::error!("{:?} Panic: No additional information", name);
+    std::panic::catch_unwind(cb).ok()
+}
+
+/// Wraps the execution of an `Eprog` (a shell function body or `eval`d code), mirroring
+/// zsh's own `addwrapper` chain: each node forwards to `w->next` until the chain is
+/// exhausted, at which point the real execution (set up by zsh core before the first node)
+/// runs. There is no argument vector here to rewrite — only the name being run.
+extern "C" fn wrapper_callback(prog: zsys::Eprog, w: zsys::FuncWrap, name: *mut c_char) -> c_int {
+    handle_panic(|| {
+        let cname = unsafe { CStr::from_ptr(name) };
+        let name_str = cname.to_str().expect("Failed to parse wrapped command name");
+
+        let mut module = get_mod();
+        let crate::Module {
+            wrappertable,
+            user_data,
+            ..
+        } = &mut *module;
+        let action = wrappertable.call(&mut **user_data, name_str);
+        drop(module);
+
+        match action {
+            WrapperAction::Forward => unsafe {
+                let next = (*w).next;
+                match next.as_ref().and_then(|n| n.wrapper) {
+                    Some(next_wrapper) => next_wrapper(prog, next, name),
+                    None => 0,
+                }
+            },
+            WrapperAction::ShortCircuit(Ok(())) => 0,
+            WrapperAction::ShortCircuit(Err(e)) => {
+                log::error_named(cname, to_cstr(e.to_string()));
+                1
             }
-            None
         }
-    }
+    })
+    .unwrap_or(65)
 }
 
 extern "Rust" {
@@ -154,7 +315,7 @@ macro_rules! mod_fn {
     (fn $name:ident($mod:ident $(,$arg:ident : $type:ty)*) $block:expr) => {
         #[no_mangle]
         extern "C" fn $name($mod: $crate::zsys::Module $(,$arg: $type)*) -> i32 {
-            handle_panic(unsafe { mod_get_name($mod) }.to_str().unwrap(), || {
+            handle_panic(|| {
                 $block
             }).unwrap_or(65)
         }
@@ -163,6 +324,7 @@ macro_rules! mod_fn {
 
 mod_fn!(
     fn setup_(_mod) {
+        install_panic_hook();
         let mut module = match unsafe { __zsh_rust_setup() } {
             Ok(module) => module,
             Err(e) => {
@@ -173,15 +335,38 @@ mod_fn!(
         for x in module.features.get_binaries() {
             x.handlerfunc = Some(builtin_callback)
         }
+        for x in module.features.get_params() {
+            x.getfn = Some(param_get);
+            x.setfn = Some(param_set);
+        }
+        for x in module.features.get_mathfns() {
+            x.func = Some(mathfn_callback)
+        }
+        module.wrappertable.install(wrapper_callback);
         set_mod(module);
         0
     }
 );
 
+fn load_dependency(mod_: zsys::Module, name: &str) -> Result<(), crate::Zerror> {
+    let cname = to_cstr(name);
+    let loaded = unsafe { zsys::require_named(mod_, cname.as_ptr()) };
+    if loaded == 0 {
+        Ok(())
+    } else {
+        Err(crate::Zerror::ModuleLoadFailed(name.to_string()))
+    }
+}
+
 mod_fn!(
     fn boot_(_mod) try {
-        // zsys::addwrapper()
-        Ok::<_, std::convert::Infallible>(())
+        let mut module = get_mod();
+        for name in &module.dependencies {
+            load_dependency(_mod, name)?;
+        }
+        let w = module.wrappertable.as_funcwrap();
+        unsafe { zsys::addwrapper(_mod, w) };
+        Ok::<_, crate::Zerror>(())
     }
 );
 
@@ -206,7 +391,9 @@ mod_fn!(
 mod_fn!(
     fn cleanup_(_mod) {
         let mut module = get_mod();
+        let w = module.wrappertable.as_funcwrap();
         unsafe {
+            zsys::deletewrapper(_mod, w);
             zsys::setfeatureenables(_mod, &mut *module.features, std::ptr::null_mut())
         }
     }
@@ -0,0 +1,72 @@
+use zsh_sys as zsys;
+
+/// Owns the raw zsh feature-table arrays and mediates registering them with zsh core.
+///
+/// Mirrors zsh's `features` struct, but keeps the backing storage alive on the Rust side so
+/// `featuresarray`/`handlefeatures` have something stable to point at for the lifetime of the
+/// module. Binaries, parameters and math functions are held; the condition table is left
+/// empty.
+pub struct Features {
+    raw: zsys::features,
+    // Boxed slices (rather than `Vec`s) so the backing storage never reallocates and moves
+    // out from under the raw pointers `raw` is about to be pointed at.
+    binaries: Box<[zsys::builtin]>,
+    params: Box<[zsys::paramdef]>,
+    mathfns: Box<[zsys::mathfunc]>,
+}
+
+impl Features {
+    pub(crate) fn new(
+        binaries: Vec<zsys::builtin>,
+        params: Vec<zsys::paramdef>,
+        mathfns: Vec<zsys::mathfunc>,
+    ) -> Self {
+        let mut binaries = binaries.into_boxed_slice();
+        let mut params = params.into_boxed_slice();
+        let mut mathfns = mathfns.into_boxed_slice();
+
+        let mut raw: zsys::features = unsafe { std::mem::zeroed() };
+        raw.bn_list = binaries.as_mut_ptr();
+        raw.bn_size = binaries.len() as _;
+        raw.pd_list = params.as_mut_ptr();
+        raw.pd_size = params.len() as _;
+        raw.mf_list = mathfns.as_mut_ptr();
+        raw.mf_size = mathfns.len() as _;
+
+        Self {
+            raw,
+            binaries,
+            params,
+            mathfns,
+        }
+    }
+
+    /// The raw `builtin` entries, for attaching `handlerfunc` trampolines.
+    pub(crate) fn get_binaries(&mut self) -> &mut [zsys::builtin] {
+        &mut self.binaries
+    }
+
+    /// The raw `paramdef` entries, for attaching getter/setter trampolines.
+    pub(crate) fn get_params(&mut self) -> &mut [zsys::paramdef] {
+        &mut self.params
+    }
+
+    /// The raw `mathfunc` entries, for attaching the math-function trampoline.
+    pub(crate) fn get_mathfns(&mut self) -> &mut [zsys::mathfunc] {
+        &mut self.mathfns
+    }
+}
+
+impl std::ops::Deref for Features {
+    type Target = zsys::features;
+
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
+impl std::ops::DerefMut for Features {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.raw
+    }
+}
@@ -0,0 +1,58 @@
+use std::{
+    borrow::Cow,
+    ffi::{CStr, CString},
+};
+
+mod error;
+mod export_module;
+mod features;
+mod module;
+pub mod log;
+pub mod options;
+pub mod types;
+
+pub use error::Zerror;
+pub use export_module::{clear_last_panic, last_panic, PanicRecord};
+pub use module::Module;
+pub use types::FilePath;
+
+/// A boxed error type used where callers don't need a concrete error type.
+pub type AnyError = Box<dyn std::error::Error>;
+
+/// Converts a value into an owned, C-compatible string for handing to zsh's API.
+pub trait ToCString {
+    fn into_cstr<'a>(self) -> Cow<'a, CStr>
+    where
+        Self: 'a;
+}
+
+impl ToCString for String {
+    fn into_cstr<'a>(self) -> Cow<'a, CStr>
+    where
+        Self: 'a,
+    {
+        Cow::Owned(CString::new(self).expect("string contained a NUL byte"))
+    }
+}
+
+impl ToCString for &str {
+    fn into_cstr<'a>(self) -> Cow<'a, CStr>
+    where
+        Self: 'a,
+    {
+        Cow::Owned(CString::new(self).expect("string contained a NUL byte"))
+    }
+}
+
+/// Converts any [`ToCString`] value into an owned [`CString`].
+pub fn to_cstr<T: ToCString>(value: T) -> CString {
+    value.into_cstr().into_owned()
+}
+
+/// Logs a formatted error with no specific attribution.
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::log::error(format!($($arg)*))
+    };
+}
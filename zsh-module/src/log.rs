@@ -0,0 +1,18 @@
+use std::ffi::CStr;
+
+use zsh_sys as zsys;
+
+/// Logs an error attributed to `name` (typically a builtin, parameter, or module name).
+pub fn error_named(name: &CStr, message: impl AsRef<CStr>) {
+    unsafe {
+        zsys::zwarnnam(name.as_ptr(), message.as_ref().as_ptr());
+    }
+}
+
+/// Logs an error with no specific attribution.
+pub fn error(message: impl AsRef<str>) {
+    let msg = crate::to_cstr(message.as_ref().to_owned());
+    unsafe {
+        zsys::zwarn(msg.as_ptr());
+    }
+}
@@ -0,0 +1,171 @@
+use std::{any::Any, collections::HashMap, ffi::CStr};
+
+use zsh_sys as zsys;
+
+use crate::{features::Features, options::Opts, AnyError};
+
+/// A builtin command handler.
+pub type BuiltinFn =
+    Box<dyn FnMut(&mut dyn Any, &str, &[&str], Opts) -> Result<(), AnyError> + Send>;
+
+/// A parameter getter, producing the current value of a `$NAME`-backed shell parameter.
+pub type ParamGetFn = Box<dyn FnMut(&mut dyn Any) -> Result<String, AnyError> + Send>;
+
+/// A parameter setter, called when the shell assigns to a `$NAME`-backed parameter, or
+/// unsets it (`value` is `None`).
+pub type ParamSetFn = Box<dyn FnMut(&mut dyn Any, Option<&str>) -> Result<(), AnyError> + Send>;
+
+/// A registered math function, as used from `$(( myfunc(args) ))`.
+pub type MathFn = Box<dyn FnMut(&mut dyn Any, &[&str]) -> Result<f64, AnyError> + Send>;
+
+/// Maps builtin command names to their Rust handlers.
+#[derive(Default)]
+pub struct BinTable(HashMap<String, BuiltinFn>);
+
+impl BinTable {
+    pub fn insert(&mut self, name: impl Into<String>, handler: BuiltinFn) {
+        self.0.insert(name.into(), handler);
+    }
+
+    pub(crate) fn get_mut(&mut self, name: &CStr) -> Option<&mut BuiltinFn> {
+        self.0.get_mut(name.to_str().ok()?)
+    }
+}
+
+/// Maps shell parameter names to their Rust getter/setter pair.
+#[derive(Default)]
+pub struct ParamTable(HashMap<String, (ParamGetFn, ParamSetFn)>);
+
+impl ParamTable {
+    pub fn insert(&mut self, name: impl Into<String>, get: ParamGetFn, set: ParamSetFn) {
+        self.0.insert(name.into(), (get, set));
+    }
+
+    pub(crate) fn get_mut(&mut self, name: &CStr) -> Option<&mut (ParamGetFn, ParamSetFn)> {
+        self.0.get_mut(name.to_str().ok()?)
+    }
+}
+
+/// Maps math function names (as used in `$(( ... ))`) to their Rust implementation.
+#[derive(Default)]
+pub struct MathFnTable(HashMap<String, MathFn>);
+
+impl MathFnTable {
+    pub fn insert(&mut self, name: impl Into<String>, handler: MathFn) {
+        self.0.insert(name.into(), handler);
+    }
+
+    pub(crate) fn get_mut(&mut self, name: &CStr) -> Option<&mut MathFn> {
+        self.0.get_mut(name.to_str().ok()?)
+    }
+}
+
+/// What a [`WrapperFn`] wants to happen to the function/eval it was invoked around.
+pub enum WrapperAction {
+    /// Let the invocation proceed: the next wrapper in zsh's chain, or the real execution
+    /// once the chain is exhausted.
+    Forward,
+    /// Don't let the invocation proceed; resolve with this result instead.
+    ShortCircuit(Result<(), AnyError>),
+}
+
+/// A function-wrapper callback, run around every shell function (or `eval`d code) invocation
+/// zsh lets this module's wrapper intercept. Builtins are not wrapped.
+///
+/// zsh's wrapper mechanism wraps the execution of an `Eprog`, not a builtin-style call with
+/// an argument vector, so this only gets the name being run; it decides whether to let the
+/// invocation proceed.
+pub type WrapperFn = Box<dyn FnMut(&mut dyn Any, &str) -> WrapperAction + Send>;
+
+/// Owns the `funcwrap` node zsh links into its wrapper chain via `addwrapper`, plus the
+/// Rust callbacks run from the module's `wrapper_callback` trampoline.
+///
+/// zsh's `FuncWrap` is itself a pointer (`*mut struct funcwrap`) threaded through an
+/// intrusive `next` chain, so unlike [`Features`], this keeps the node behind a `Box` for a
+/// stable address and hands out the raw pointer rather than implementing `Deref`.
+pub struct WrapperTable {
+    node: Box<zsys::funcwrap>,
+    callbacks: Vec<WrapperFn>,
+}
+
+impl Default for WrapperTable {
+    fn default() -> Self {
+        Self {
+            node: Box::new(unsafe { std::mem::zeroed() }),
+            callbacks: Vec::new(),
+        }
+    }
+}
+
+impl WrapperTable {
+    pub fn push(&mut self, callback: WrapperFn) {
+        self.callbacks.push(callback);
+    }
+
+    pub(crate) fn call(&mut self, user_data: &mut dyn Any, name: &str) -> WrapperAction {
+        let mut action = WrapperAction::Forward;
+        for callback in &mut self.callbacks {
+            action = callback(user_data, name);
+            if matches!(action, WrapperAction::ShortCircuit(_)) {
+                break;
+            }
+        }
+        action
+    }
+
+    /// Points the node's handler at `handler` and returns the raw `FuncWrap` node for
+    /// `addwrapper`/`deletewrapper`.
+    pub(crate) fn install(&mut self, handler: zsys::Wrapperfn) -> zsys::FuncWrap {
+        self.node.wrapper = Some(handler);
+        self.node.as_mut() as *mut zsys::funcwrap
+    }
+
+    /// The raw `FuncWrap` node, for `addwrapper`/`deletewrapper` once `install` has set its
+    /// handler.
+    pub(crate) fn as_funcwrap(&mut self) -> zsys::FuncWrap {
+        self.node.as_mut() as *mut zsys::funcwrap
+    }
+}
+
+/// A loaded zsh module: the feature tables zsh core registers, the Rust handlers that back
+/// them, and whatever user state the module's `setup` function wants to carry around.
+pub struct Module {
+    pub(crate) features: Features,
+    pub(crate) bintable: BinTable,
+    pub(crate) paramtable: ParamTable,
+    pub(crate) mathfntable: MathFnTable,
+    pub(crate) wrappertable: WrapperTable,
+    /// Other modules (e.g. `zsh/parameter`) that must be loaded before `boot_` finishes.
+    ///
+    /// Populated via [`Module::require`]; actually loaded by `boot_` once zsh hands us a
+    /// module handle to load them against.
+    pub(crate) dependencies: Vec<String>,
+    pub user_data: Box<dyn Any>,
+}
+
+impl Module {
+    pub fn new(
+        features: Features,
+        bintable: BinTable,
+        paramtable: ParamTable,
+        mathfntable: MathFnTable,
+        wrappertable: WrapperTable,
+        user_data: Box<dyn Any>,
+    ) -> Self {
+        Self {
+            features,
+            bintable,
+            paramtable,
+            mathfntable,
+            wrappertable,
+            dependencies: Vec::new(),
+            user_data,
+        }
+    }
+
+    /// Declares that `name` (e.g. `"zsh/parameter"`) must be loaded before this module
+    /// finishes booting. Call this from the user-provided `setup` function.
+    pub fn require(&mut self, name: impl Into<String>) {
+        self.dependencies.push(name.into());
+    }
+}
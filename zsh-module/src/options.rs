@@ -0,0 +1,25 @@
+use zsh_sys as zsys;
+
+/// A handle to the options a builtin was invoked with.
+///
+/// Thin wrapper around zsh's raw `options` table, so builtin callbacks get a typed
+/// handle instead of a bare pointer.
+#[derive(Debug, Clone, Copy)]
+pub struct Opts {
+    raw: *mut zsys::options,
+}
+
+impl Opts {
+    /// Wraps a raw options pointer handed to us by zsh.
+    ///
+    /// # Safety
+    /// `raw` must be valid for at least the lifetime of the callback that receives it.
+    pub unsafe fn from_raw(raw: *mut zsys::options) -> Self {
+        Self { raw }
+    }
+
+    /// Returns whether the given option letter was passed to the builtin.
+    pub fn has(&self, letter: u8) -> bool {
+        unsafe { zsys::optused(self.raw, letter as i32) != 0 }
+    }
+}
@@ -1,13 +1,44 @@
-use std::{fs::DirEntry, path::*, str::FromStr};
+use std::{
+    fs::{DirEntry, Metadata},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+    path::*,
+    str::FromStr,
+    time::SystemTime,
+};
 
 use crate::{ToCString, Zerror};
 
+/// An owned file descriptor opened from a [`FilePath`], ready to hand to zsh's C APIs.
+///
+/// Closes the descriptor when dropped. Callers that need the fd number as a string (e.g. to
+/// pass to a builtin) must keep this handle alive for as long as they use that number, since
+/// the descriptor closes as soon as the handle is dropped.
+#[derive(Debug)]
+pub struct FileHandle(OwnedFd);
+
+impl FileHandle {
+    /// Borrows this handle's descriptor without giving up ownership.
+    pub fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+
+    /// The raw fd number. Only valid for as long as `self` is alive.
+    pub fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
 /// A helper struct to represent an owned filepath
 ///
-/// Caches the internal path, as well as the display string and its character length.
+/// Caches the internal path, as well as the display string, its character length, and
+/// metadata (type, size, modified time) taken at construction time.
 ///
 /// All methods for creating this type will check if the filepath exists, and fail if it does not, unless otherwise specified.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// `PartialEq`/`Eq`/`Ord`/`Hash` compare only `path`: the cached metadata is a snapshot that
+/// can go stale without the path itself changing, and two `FilePath`s for the same path
+/// should still agree regardless of what the file looked like when each was constructed.
+#[derive(Debug, Clone)]
 pub struct FilePath {
     /// The path of the file
     pub path: PathBuf,
@@ -15,6 +46,36 @@ pub struct FilePath {
     pub string: String,
     /// The length of this path in characters
     pub length: usize,
+    /// Whether the path is a directory
+    pub is_dir: bool,
+    /// Whether the path itself is a symlink (not whether it points through one)
+    pub is_symlink: bool,
+    /// The size of the file in bytes, as of construction
+    pub len: u64,
+    /// The last modified time, as of construction, if the platform supports it
+    pub modified: Option<SystemTime>,
+}
+
+impl PartialEq for FilePath {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+impl Eq for FilePath {}
+impl PartialOrd for FilePath {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for FilePath {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.path.cmp(&other.path)
+    }
+}
+impl std::hash::Hash for FilePath {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.path.hash(state);
+    }
 }
 impl FilePath {
     /// Create a new, owned, checked, filepath. This is the preferred way to create this type.
@@ -29,7 +90,15 @@ impl FilePath {
 
         let string = path.to_string_lossy().to_string();
         let length = string.chars().count();
+        let metadata = std::fs::symlink_metadata(&path).ok();
         Ok(Self {
+            is_dir: metadata.as_ref().map(Metadata::is_dir).unwrap_or(false),
+            is_symlink: metadata
+                .as_ref()
+                .map(|m| m.file_type().is_symlink())
+                .unwrap_or(false),
+            len: metadata.as_ref().map(Metadata::len).unwrap_or(0),
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()),
             path,
             string,
             length,
@@ -43,7 +112,15 @@ impl FilePath {
         let path = pathlike.as_ref().to_path_buf();
         let string = path.to_string_lossy().to_string();
         let length = string.chars().count();
+        let metadata = std::fs::symlink_metadata(&path).ok();
         Self {
+            is_dir: metadata.as_ref().map(Metadata::is_dir).unwrap_or(false),
+            is_symlink: metadata
+                .as_ref()
+                .map(|m| m.file_type().is_symlink())
+                .unwrap_or(false),
+            len: metadata.as_ref().map(Metadata::len).unwrap_or(0),
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()),
             path,
             string,
             length,
@@ -57,6 +134,35 @@ impl FilePath {
         self = Self::new(new_pathlike_value)?;
         Ok(())
     }
+    /// Resolves the path to its canonical, absolute form before caching it.
+    pub fn canonicalize<P>(pathlike: P) -> Result<Self, Zerror>
+    where
+        P: AsRef<Path>,
+    {
+        let path = pathlike.as_ref();
+        let real = path
+            .canonicalize()
+            .map_err(|_| Zerror::FileNotFound(path.to_path_buf()))?;
+        Self::new(real)
+    }
+    /// Like [`FilePath::new`], but if the path is a symlink, resolves and caches its target
+    /// instead of the link itself.
+    ///
+    /// Resolves through `canonicalize` rather than a single `read_link` hop, since a
+    /// symlink's target is relative to its own parent directory (not the process's cwd) and
+    /// may itself be another symlink.
+    pub fn follow_symlinks<P>(pathlike: P) -> Result<Self, Zerror>
+    where
+        P: AsRef<Path>,
+    {
+        Self::canonicalize(pathlike)
+    }
+    /// Opens this path, returning an owned file descriptor suitable for handing to zsh's C APIs.
+    pub fn open(&self) -> Result<FileHandle, Zerror> {
+        let file = std::fs::File::open(&self.path)
+            .map_err(|_| Zerror::FileNotFound(self.path.clone()))?;
+        Ok(FileHandle(OwnedFd::from(file)))
+    }
 }
 
 impl std::fmt::Display for FilePath {